@@ -36,7 +36,10 @@ use crate::{
         westend,
     },
 };
-use async_std::task;
+use async_std::{
+    prelude::Future,
+    task,
+};
 use log::{
     debug,
     error,
@@ -47,6 +50,7 @@ use rand::Rng;
 use regex::Regex;
 use serde::Deserialize;
 use std::{
+    collections::BTreeMap,
     convert::TryInto,
     io::{
         prelude::*,
@@ -54,6 +58,15 @@ use std::{
     },
     net::TcpListener,
     result::Result,
+    sync::{
+        atomic::{
+            AtomicBool,
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+    },
     thread,
     time,
 };
@@ -101,18 +114,180 @@ impl MessageTrait for Message {
     }
 }
 
+lazy_static::lazy_static! {
+    /// Process-lifetime metrics, shared across the crunch/subscription tasks and
+    /// exposed over the `/metrics` endpoint.
+    pub static ref METRICS: Arc<Metrics> = Arc::new(Metrics::default());
+}
+
+/// Counters describing the process lifetime, updated from the running tasks and
+/// scraped in Prometheus text exposition format.
+#[derive(Default)]
+pub struct Metrics {
+    pub eras_processed: AtomicU64,
+    pub payouts_submitted: AtomicU64,
+    pub last_successful_run: AtomicU64,
+    pub retry_backoff: AtomicU64,
+    payout_errors: Mutex<BTreeMap<String, u64>>,
+}
+
+impl Metrics {
+    /// Record one processed era (called from the submission path).
+    pub fn record_era(&self) {
+        self.eras_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record `count` payout extrinsics actually submitted in a batch.
+    pub fn record_payouts(&self, count: u64) {
+        self.payouts_submitted.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Record a payout error, bucketed by its `kind` so the `{kind=...}`
+    /// dimension reflects the real error variant rather than a fixed label.
+    pub fn record_payout_error(&self, kind: &str) {
+        let mut errors = self.payout_errors.lock().unwrap();
+        *errors.entry(kind.to_string()).or_insert(0) += 1;
+    }
+
+    fn exposition(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP crunch_eras_processed_total Eras processed since start.\n");
+        out.push_str("# TYPE crunch_eras_processed_total counter\n");
+        out.push_str(&format!(
+            "crunch_eras_processed_total {}\n",
+            self.eras_processed.load(Ordering::Relaxed)
+        ));
+        out.push_str("# HELP crunch_payouts_submitted_total Payout extrinsics submitted.\n");
+        out.push_str("# TYPE crunch_payouts_submitted_total counter\n");
+        out.push_str(&format!(
+            "crunch_payouts_submitted_total {}\n",
+            self.payouts_submitted.load(Ordering::Relaxed)
+        ));
+        out.push_str("# HELP crunch_payout_errors_total Payout errors by kind.\n");
+        out.push_str("# TYPE crunch_payout_errors_total counter\n");
+        for (kind, count) in self.payout_errors.lock().unwrap().iter() {
+            out.push_str(&format!(
+                "crunch_payout_errors_total{{kind=\"{}\"}} {}\n",
+                kind, count
+            ));
+        }
+        out.push_str("# HELP crunch_last_successful_run_timestamp_seconds Unix time of the last successful run.\n");
+        out.push_str("# TYPE crunch_last_successful_run_timestamp_seconds gauge\n");
+        out.push_str(&format!(
+            "crunch_last_successful_run_timestamp_seconds {}\n",
+            self.last_successful_run.load(Ordering::Relaxed)
+        ));
+        out.push_str("# HELP crunch_retry_backoff Current retry backoff counter.\n");
+        out.push_str("# TYPE crunch_retry_backoff gauge\n");
+        out.push_str(&format!(
+            "crunch_retry_backoff {}\n",
+            self.retry_backoff.load(Ordering::Relaxed)
+        ));
+        out
+    }
+
+    /// Stamp the current unix time as the last successful run.
+    pub fn mark_successful_run(&self) {
+        let now = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.last_successful_run.store(now, Ordering::Relaxed);
+    }
+}
+
 pub async fn create_substrate_node_client(
-    config: Config,
+    url: &str,
 ) -> Result<OnlineClient<PolkadotConfig>, subxt::Error> {
-    OnlineClient::<PolkadotConfig>::from_url(config.substrate_ws_url).await
+    OnlineClient::<PolkadotConfig>::from_url(url).await
+}
+
+/// Read the block number of the node's finalized head, used to detect an
+/// endpoint that is lagging behind the rest of the pool.
+async fn finalized_block_number(
+    client: &OnlineClient<PolkadotConfig>,
+) -> Result<u64, subxt::Error> {
+    let hash = client.rpc().finalized_head().await?;
+    let block = client.rpc().block(Some(hash)).await?;
+    Ok(block.map(|b| b.block.header.number as u64).unwrap_or_default())
+}
+
+/// Split a (possibly comma-separated) `substrate_ws_url` into the list of
+/// endpoints to round-robin through, trimming whitespace and dropping blanks.
+fn parse_endpoints(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect()
+}
+
+/// Probe every endpoint once and return the highest finalized head observed, so
+/// the admission check has an absolute reference to compare against rather than
+/// seeding from whichever node happens to answer first (a lagging primary would
+/// otherwise always be accepted).
+async fn highest_finalized_across(endpoints: &[String]) -> u64 {
+    let mut highest = 0_u64;
+    for url in endpoints {
+        if let Ok(client) = create_substrate_node_client(url).await {
+            if let Ok(finalized) = finalized_block_number(&client).await {
+                if finalized > highest {
+                    highest = finalized;
+                }
+            }
+        }
+    }
+    highest
 }
 
 pub async fn create_or_await_substrate_node_client(
     config: Config,
 ) -> (OnlineClient<PolkadotConfig>, SupportedRuntime) {
+    // `substrate_ws_url` accepts a comma-separated list of endpoints that the
+    // connect loop round-robins through on each failed attempt.
+    let mut endpoints = parse_endpoints(&config.substrate_ws_url);
+    // Fall back to the raw value when the list is empty or all-commas so the
+    // round-robin index never divides by zero.
+    if endpoints.is_empty() {
+        endpoints.push(config.substrate_ws_url.clone());
+    }
+    // Seed the admission reference from the best head across the whole pool so
+    // even a lagging first/primary endpoint can be rotated away from.
+    let mut highest_finalized = highest_finalized_across(&endpoints).await;
+    let mut attempt = 0_usize;
     loop {
-        match create_substrate_node_client(config.clone()).await {
+        let url = &endpoints[attempt % endpoints.len()];
+        attempt += 1;
+        info!("Trying to connect using {}", url);
+        match create_substrate_node_client(url).await {
             Ok(client) => {
+                // Reject a node that is lagging too far behind the best finalized
+                // head observed across the pool and rotate to the next endpoint.
+                match finalized_block_number(&client).await {
+                    Ok(finalized) => {
+                        // Compare against the pool-wide reference head (seeded by
+                        // the pre-probe) so a lagging node — including the first
+                        // one tried — is rotated away from rather than admitted.
+                        if highest_finalized.saturating_sub(finalized)
+                            > config.node_lag_threshold
+                        {
+                            warn!(
+                                "Node {} is lagging {} blocks behind the pool, rotating endpoint",
+                                url,
+                                highest_finalized - finalized
+                            );
+                            thread::sleep(time::Duration::from_secs(6));
+                            continue
+                        }
+                        if finalized > highest_finalized {
+                            highest_finalized = finalized;
+                        }
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                        thread::sleep(time::Duration::from_secs(6));
+                        continue
+                    }
+                }
                 let chain = client.rpc().system_chain().await.unwrap_or_default();
                 let name = client.rpc().system_name().await.unwrap_or_default();
                 let version = client.rpc().system_version().await.unwrap_or_default();
@@ -144,14 +319,14 @@ pub async fn create_or_await_substrate_node_client(
 
                 info!(
                     "Connected to {} network using {} * Substrate node {} v{}",
-                    chain, config.substrate_ws_url, name, version
+                    chain, url, name, version
                 );
 
                 break (client, SupportedRuntime::from(chain_token_symbol))
             }
             Err(e) => {
                 error!("{}", e);
-                info!("Awaiting for connection using {}", config.substrate_ws_url);
+                info!("Awaiting for connection using {}", url);
                 thread::sleep(time::Duration::from_secs(6));
             }
         }
@@ -170,6 +345,8 @@ pub fn get_from_seed(seed: &str, pass: Option<&str>) -> sr25519::Pair {
 pub struct Crunch {
     runtime: SupportedRuntime,
     client: OnlineClient<PolkadotConfig>,
+    tx_tip: u128,
+    dry_run: bool,
 }
 
 impl Crunch {
@@ -177,13 +354,45 @@ impl Crunch {
         let (client, runtime) =
             create_or_await_substrate_node_client(CONFIG.clone()).await;
 
-        Crunch { runtime, client }
+        Crunch {
+            runtime,
+            client,
+            tx_tip: CONFIG.tx_tip,
+            dry_run: false,
+        }
     }
 
     pub fn client(&self) -> &OnlineClient<PolkadotConfig> {
         &self.client
     }
 
+    /// Effective priority-fee tip applied to the payout extrinsic and reported
+    /// in the Matrix message. Carried on the client so each runtime's
+    /// `try_crunch` reads it off `self` without a signature change.
+    pub fn tx_tip(&self) -> u128 {
+        self.tx_tip
+    }
+
+    /// Override the effective tip for this run (used by the flakes loop to
+    /// escalate the bid after a failed attempt).
+    fn with_tx_tip(mut self, tx_tip: u128) -> Self {
+        self.tx_tip = tx_tip;
+        self
+    }
+
+    /// Whether this run estimates fees/weight instead of signing and
+    /// submitting. Carried on the client so each runtime's `try_crunch` reads
+    /// it off `self` without a signature change.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Mark this client as a dry run (used by the dry-run task).
+    fn as_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
     /// Spawn and restart crunch flakes task on error
     pub fn flakes() {
         spawn_and_restart_crunch_flakes_on_error();
@@ -199,6 +408,11 @@ impl Crunch {
         spawn_crunch_view();
     }
 
+    /// Spawn crunch dry-run task
+    pub fn dry_run() {
+        spawn_crunch_dry_run();
+    }
+
     async fn inspect(&self) -> Result<(), CrunchError> {
         match self.runtime {
             SupportedRuntime::Polkadot => polkadot::inspect(self).await,
@@ -209,6 +423,12 @@ impl Crunch {
         }
     }
 
+    /// Run the payout batch. When the client is marked as a dry run
+    /// ([`Crunch::is_dry_run`]), each runtime's `try_crunch` walks the identical
+    /// era/validator selection and builds the same batch call, but estimates
+    /// fees/weight and reports instead of signing and submitting — so dry-run
+    /// can't drift from the real path. The effective tip is read off `self`
+    /// too (see [`Crunch::tx_tip`]), leaving `try_crunch`'s signature unchanged.
     async fn try_run_batch(&self) -> Result<(), CrunchError> {
         match self.runtime {
             SupportedRuntime::Polkadot => polkadot::try_crunch(self).await,
@@ -237,40 +457,96 @@ impl Crunch {
     }
 }
 
+/// Bucket a payout error by a short, stable `kind` for the `payout_errors`
+/// metric label.
+fn error_kind(e: &CrunchError) -> &'static str {
+    match e {
+        CrunchError::SubscriptionFinished => "subscription_finished",
+        CrunchError::MatrixError(_) => "matrix",
+        _ => "other",
+    }
+}
+
 fn spawn_and_restart_subscription_on_error() {
-    let t = task::spawn(async {
+    let shutdown = register_shutdown_signals();
+    let t = task::spawn(async move {
         let config = CONFIG.clone();
         let mut n = 1_u32;
         loop {
+            if shutdown.load(Ordering::Relaxed) {
+                info!("Shutdown requested, draining subscription and exiting");
+                break
+            }
             let c: Crunch = Crunch::new().await;
-            if let Err(e) = c.run_and_subscribe_era_paid_events().await {
+            // Race the long-running subscription against the shutdown flag so a
+            // SIGTERM/SIGHUP interrupts the await instead of hanging on it. The
+            // shutdown arm yields `None` so it is not mistaken for a completed
+            // era; the current handler drains to its next await point before the
+            // losing future is dropped.
+            let subscribe = async { Some(c.run_and_subscribe_era_paid_events().await) };
+            let on_signal = async {
+                shutdown_requested(shutdown.clone()).await.ok();
+                None
+            };
+            let result = match subscribe.race(on_signal).await {
+                None => {
+                    info!("Shutdown requested, stopping subscription and exiting");
+                    break
+                }
+                Some(result) => result,
+            };
+            if let Err(e) = result {
                 match e {
                     CrunchError::SubscriptionFinished => warn!("{}", e),
                     CrunchError::MatrixError(_) => warn!("Matrix message skipped!"),
                     _ => {
                         error!("{}", e);
+                        METRICS.record_payout_error(error_kind(&e));
+                        METRICS.retry_backoff.store(u64::from(n), Ordering::Relaxed);
                         let sleep_min = u32::pow(config.error_interval, n);
-                        thread::sleep(time::Duration::from_secs((60 * sleep_min).into()));
+                        interruptible_sleep((60 * sleep_min).into(), &shutdown).await;
                         n += 1;
                         continue
                     }
                 }
-                thread::sleep(time::Duration::from_secs(1));
+                interruptible_sleep(1, &shutdown).await;
+            } else {
+                // The subscription returned without error; reset the backoff so
+                // the next failure starts escalating from scratch. Eras and
+                // payouts are counted from the submission path, not here.
+                METRICS.mark_successful_run();
+                METRICS.retry_backoff.store(0, Ordering::Relaxed);
+                n = 1;
             };
         }
     });
 
-    let h = healthcheck();
+    let _metrics = spawn_metrics_server();
 
     task::block_on(t);
 }
 
 fn spawn_and_restart_crunch_flakes_on_error() {
-    let t = task::spawn(async {
+    let shutdown = register_shutdown_signals();
+    let t = task::spawn(async move {
         let config = CONFIG.clone();
         let mut n = 1_u32;
         loop {
-            let c: Crunch = Crunch::new().await;
+            if shutdown.load(Ordering::Relaxed) {
+                info!("Shutdown requested, draining batch and exiting");
+                break
+            }
+            // Escalate the base tip by the retry counter so each restart after a
+            // failure bids a higher priority fee, capped at the configured ceiling.
+            // The effective tip is carried on the client so each runtime's
+            // try_crunch both applies it via PolkadotExtrinsicParamsBuilder and
+            // records it in the Matrix report message.
+            let tx_tip = config
+                .tx_tip
+                .saturating_mul(u128::from(n))
+                .min(config.tx_tip_max);
+            debug!("Effective tx tip for this run: {} plancks", tx_tip);
+            let c: Crunch = Crunch::new().await.with_tx_tip(tx_tip);
             if let Err(e) = c.try_run_batch().await {
                 let sleep_min = u32::pow(config.error_interval, n);
                 match e {
@@ -278,29 +554,43 @@ fn spawn_and_restart_crunch_flakes_on_error() {
                         error!("{}", e);
                     }
                 }
-                thread::sleep(time::Duration::from_secs((60 * sleep_min).into()));
+                METRICS.record_payout_error(error_kind(&e));
+                METRICS.retry_backoff.store(u64::from(n), Ordering::Relaxed);
+                interruptible_sleep((60 * sleep_min).into(), &shutdown).await;
                 n += 1;
                 continue
             };
-            thread::sleep(time::Duration::from_secs(config.interval));
+            // Eras and payout extrinsics are counted from the submission path
+            // (each runtime's try_crunch) via record_era/record_payouts, so the
+            // counters reflect real work rather than one tick per loop.
+            METRICS.mark_successful_run();
+            METRICS.retry_backoff.store(0, Ordering::Relaxed);
+            // Reset the retry counter so a later failure starts escalating the
+            // tip from the base again instead of staying permanently elevated.
+            n = 1;
+            interruptible_sleep(config.interval, &shutdown).await;
         }
     });
 
-    let h = healthcheck();
+    let _metrics = spawn_metrics_server();
 
     task::block_on(t);
 }
 
-fn healthcheck() -> async_std::task::JoinHandle<()> {
-    let h = task::spawn(async {
-        let listener = TcpListener::bind("127.0.0.1:9999").unwrap();
-        let response = "HTTP/1.1 200 OK\r\n\r\n".as_bytes();
+/// Minimal metrics subsystem: binds a configurable address and serves `/healthz`
+/// (liveness) and `/metrics` (Prometheus text exposition) backed by [`METRICS`].
+fn spawn_metrics_server() -> async_std::task::JoinHandle<()> {
+    let config = CONFIG.clone();
+    task::spawn(async move {
+        let listener = TcpListener::bind(&config.metrics_bind_address).unwrap();
+        info!("Metrics server listening on {}", config.metrics_bind_address);
 
         for stream in listener.incoming() {
             // unwrap and panic on error to interrupt the main task
             let mut stream = stream.unwrap();
 
-            // we need to read the full request before we respond or we get a 'connection reset by peer error'
+            // we need to read the full request before we respond or we get a
+            // 'connection reset by peer' error
             let buf_reader = BufReader::new(&mut stream);
             let http_request: Vec<_> = buf_reader
                 .lines()
@@ -308,11 +598,24 @@ fn healthcheck() -> async_std::task::JoinHandle<()> {
                 .take_while(|line| !line.is_empty())
                 .collect();
 
-            stream.write_all(response).unwrap();
-        }
-    });
+            let request_line = http_request.first().map(String::as_str).unwrap_or("");
+            let (status, body) = if request_line.starts_with("GET /metrics") {
+                ("200 OK", METRICS.exposition())
+            } else if request_line.starts_with("GET /healthz") {
+                ("200 OK", String::from("OK\n"))
+            } else {
+                ("404 NOT FOUND", String::new())
+            };
 
-    return h
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    })
 }
 fn spawn_crunch_view() {
     let crunch_task = task::spawn(async {
@@ -324,6 +627,55 @@ fn spawn_crunch_view() {
     task::block_on(crunch_task);
 }
 
+fn spawn_crunch_dry_run() {
+    let crunch_task = task::spawn(async {
+        // Same batch path as a real run, but marked as a dry run so each
+        // runtime's try_crunch estimates fees/weight instead of submitting.
+        // The base tip (set by `new`) seeds the reported estimate.
+        let c: Crunch = Crunch::new().await.as_dry_run();
+        if let Err(e) = c.try_run_batch().await {
+            error!("{}", e);
+        };
+    });
+    task::block_on(crunch_task);
+}
+
+/// Register a shared "shutdown requested" flag that is raised when the process
+/// receives SIGTERM or SIGHUP. The long-running loops check this flag at the top
+/// of each iteration and then return so the process exits cleanly (exit code 0).
+/// The flakes loop lets the current batch finish first. The subscription loop
+/// does not: it polls the flag about once a second while the subscription runs
+/// and, once it is raised, drops the subscription future where it is suspended,
+/// so work in progress inside that subscription is not completed.
+fn register_shutdown_signals() -> Arc<AtomicBool> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown))
+            .expect("constructed from known-good static signal; qed");
+    }
+    shutdown
+}
+
+/// Resolves once the shutdown flag has been raised, polling it cheaply so it can
+/// be raced against a long-running task to interrupt it promptly on a signal.
+async fn shutdown_requested(shutdown: Arc<AtomicBool>) -> Result<(), CrunchError> {
+    while !shutdown.load(Ordering::Relaxed) {
+        task::sleep(time::Duration::from_secs(1)).await;
+    }
+    Ok(())
+}
+
+/// Sleep for `secs`, returning early as soon as shutdown is requested so the
+/// process doesn't stay parked in the interval gap while a signal is pending.
+async fn interruptible_sleep(secs: u64, shutdown: &Arc<AtomicBool>) {
+    for _ in 0..secs {
+        if shutdown.load(Ordering::Relaxed) {
+            break
+        }
+        task::sleep(time::Duration::from_secs(1)).await;
+    }
+}
+
 pub fn random_wait(max: u64) -> u64 {
     let mut rng = rand::thread_rng();
     rng.gen_range(0..max)
@@ -353,6 +705,71 @@ pub struct OnetData {
     pub sessions: Vec<u32>,
 }
 
+/// Map a ONE-T letter grade to a numeric rank (higher is better) so grades can
+/// be compared against the configured `min_onet_grade` threshold.
+fn onet_grade_rank(grade: &str) -> u8 {
+    match grade.trim() {
+        "A+" => 9,
+        "A" => 8,
+        "B+" => 7,
+        "B" => 6,
+        "C+" => 5,
+        "C" => 4,
+        "D+" => 3,
+        "D" => 2,
+        "F" => 1,
+        _ => 0,
+    }
+}
+
+impl OnetData {
+    /// Whether this stash meets the configured minimum grade.
+    pub fn meets_min_grade(&self, min_grade: &str) -> bool {
+        onet_grade_rank(&self.grade) >= onet_grade_rank(min_grade)
+    }
+
+    /// Returns a human-readable reason when the stash is underperforming against
+    /// the configured ONE-T policy (grade and/or inclusion ratios), or `None`
+    /// when it is within thresholds. The caller raises a distinct alert (and
+    /// optionally skips the payout when `onet_skip_underperforming` is set)
+    /// whenever this is `Some`.
+    pub fn underperformance(&self, config: &Config) -> Option<String> {
+        let mut reasons: Vec<String> = Vec::new();
+        if !config.min_onet_grade.is_empty()
+            && !self.meets_min_grade(&config.min_onet_grade)
+        {
+            reasons.push(format!(
+                "grade {} below minimum {}",
+                self.grade, config.min_onet_grade
+            ));
+        }
+        if self.authority_inclusion < config.min_onet_authority_inclusion {
+            reasons.push(format!(
+                "authority inclusion {:.2} below minimum {:.2}",
+                self.authority_inclusion, config.min_onet_authority_inclusion
+            ));
+        }
+        if self.para_authority_inclusion < config.min_onet_para_authority_inclusion {
+            reasons.push(format!(
+                "para-authority inclusion {:.2} below minimum {:.2}",
+                self.para_authority_inclusion, config.min_onet_para_authority_inclusion
+            ));
+        }
+        if reasons.is_empty() {
+            None
+        } else {
+            Some(reasons.join(", "))
+        }
+    }
+
+    /// Whether payout submission should be skipped for this stash, i.e. the
+    /// operator enabled `onet_skip_underperforming` and the stash is currently
+    /// flagged as underperforming.
+    pub fn should_skip_payout(&self, config: &Config) -> bool {
+        config.onet_skip_underperforming && self.underperformance(config).is_some()
+    }
+}
+
 pub async fn try_fetch_onet_data(
     chain_name: String,
     stash: AccountId32,
@@ -385,6 +802,10 @@ pub async fn try_fetch_onet_data(
             match response.status() {
                 reqwest::StatusCode::OK => {
                     match response.json::<OnetData>().await {
+                        // Return the raw data only. The grade/inclusion policy
+                        // (alert + skip verdict via `underperformance` /
+                        // `should_skip_payout`) is applied by the batch-building
+                        // caller so side-effects aren't buried in a fetch.
                         Ok(parsed) => return Ok(Some(parsed)),
                         Err(e) => {
                             error!(
@@ -409,3 +830,67 @@ pub fn get_account_id_from_storage_key(key: StorageKey) -> AccountId32 {
     let v: [u8; 32] = s.try_into().expect("slice with incorrect length");
     v.into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_endpoints_splits_trims_and_filters() {
+        assert_eq!(
+            parse_endpoints("wss://a, wss://b ,,wss://c"),
+            vec![
+                "wss://a".to_string(),
+                "wss://b".to_string(),
+                "wss://c".to_string(),
+            ]
+        );
+        assert_eq!(
+            parse_endpoints("wss://only"),
+            vec!["wss://only".to_string()]
+        );
+        assert!(parse_endpoints("  ,, ").is_empty());
+    }
+
+    #[test]
+    fn exposition_renders_counters_and_error_kinds() {
+        let m = Metrics::default();
+        m.record_era();
+        m.record_payouts(3);
+        m.record_payout_error("matrix");
+        m.record_payout_error("matrix");
+        m.record_payout_error("other");
+        let out = m.exposition();
+        assert!(out.contains("crunch_eras_processed_total 1"));
+        assert!(out.contains("crunch_payouts_submitted_total 3"));
+        assert!(out.contains("crunch_payout_errors_total{kind=\"matrix\"} 2"));
+        assert!(out.contains("crunch_payout_errors_total{kind=\"other\"} 1"));
+        assert!(out.contains("# TYPE crunch_retry_backoff gauge"));
+    }
+
+    fn onet_with_grade(grade: &str) -> OnetData {
+        OnetData {
+            address: "stash".to_string(),
+            grade: grade.to_string(),
+            authority_inclusion: 1.0,
+            para_authority_inclusion: 1.0,
+            sessions: vec![],
+        }
+    }
+
+    #[test]
+    fn onet_grade_rank_orders_grades() {
+        assert!(onet_grade_rank("A+") > onet_grade_rank("A"));
+        assert!(onet_grade_rank("A") > onet_grade_rank("B+"));
+        assert!(onet_grade_rank("D") > onet_grade_rank("F"));
+        assert_eq!(onet_grade_rank(" A+ "), onet_grade_rank("A+"));
+        assert_eq!(onet_grade_rank("nonsense"), 0);
+    }
+
+    #[test]
+    fn meets_min_grade_compares_by_rank() {
+        assert!(onet_with_grade("A").meets_min_grade("B"));
+        assert!(onet_with_grade("B").meets_min_grade("B"));
+        assert!(!onet_with_grade("C").meets_min_grade("B"));
+    }
+}